@@ -0,0 +1,62 @@
+//! Parses the frontmatter block at the top of a Markdown document, mirroring
+//! Zola's `front_matter` module: a `---`-fenced block is parsed as YAML, a
+//! `+++`-fenced block as TOML. Unrecognized keys are ignored; a block that
+//! fails to parse (malformed syntax, or a known key with a value of the wrong
+//! type) degrades to `Frontmatter::default()` instead of aborting the
+//! conversion.
+
+use serde::Deserialize;
+
+/// Page metadata that overrides the default Typst page setup.
+#[derive(Debug, Default, Deserialize)]
+pub struct Frontmatter {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub lang: Option<String>,
+    #[serde(default)]
+    pub font: Option<String>,
+    #[serde(default)]
+    pub font_size: Option<String>,
+    #[serde(default)]
+    pub paper: Option<String>,
+    #[serde(default)]
+    pub leading: Option<String>,
+}
+
+/// Splits `input` into its frontmatter (parsed according to its fence style)
+/// and the remaining Markdown body.
+pub fn parse(input: &str) -> (Frontmatter, &str) {
+    if let Some((fm, body)) = split_fenced(input, "---") {
+        return (serde_yaml::from_str(fm).unwrap_or_default(), body);
+    }
+    if let Some((fm, body)) = split_fenced(input, "+++") {
+        return (toml::from_str(fm).unwrap_or_default(), body);
+    }
+    (Frontmatter::default(), input)
+}
+
+/// Separates a leading `<fence>`-delimited block (handling both `\n` and
+/// `\r\n` line endings) from the rest of `input`.
+fn split_fenced<'a>(input: &'a str, fence: &str) -> Option<(&'a str, &'a str)> {
+    let open_crlf = format!("{}\r\n", fence);
+    let open_lf = format!("{}\n", fence);
+    let rest = input
+        .strip_prefix(&open_crlf)
+        .or_else(|| input.strip_prefix(&open_lf))?;
+
+    let close = format!("\n{}", fence);
+    let idx = rest.find(&close)?;
+    let fm = &rest[..idx];
+    let after = &rest[idx + close.len()..];
+    let body = after
+        .strip_prefix("\r\n")
+        .or_else(|| after.strip_prefix('\n'))
+        .unwrap_or(after);
+
+    Some((fm, body))
+}
@@ -0,0 +1,649 @@
+//! Converts Markdown (optionally preceded by YAML/TOML frontmatter) to
+//! Typst markup. The core logic lives here as a library, generic over
+//! `std::fmt::Write`, so it can be embedded in other programs — including
+//! WASM targets — without going through stdin/stdout; `src/main.rs` is a
+//! thin CLI built on top of it.
+
+mod front_matter;
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Tag, TagEnd};
+
+pub use front_matter::Frontmatter;
+
+/// An error produced while rendering into the caller's `fmt::Write` sink.
+#[derive(Debug)]
+pub struct Error(fmt::Error);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error rendering Typst output: {}", self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<fmt::Error> for Error {
+    fn from(e: fmt::Error) -> Self {
+        Error(e)
+    }
+}
+
+/// Converts `input` to a complete Typst document, returned as a `String`.
+pub fn markdown_to_typst(input: &str) -> String {
+    let mut out = String::new();
+    render_into(&mut out, input).expect("writing to a String never fails");
+    out
+}
+
+/// Streams the Typst output for `input` into any `fmt::Write` sink.
+pub fn render_into<W: Write>(w: &mut W, input: &str) -> Result<(), Error> {
+    let (meta, body) = front_matter::parse(input);
+    write_page_setup(w, &meta)?;
+    render_body(w, body)?;
+    Ok(())
+}
+
+/// Outputs the Typst page setup and metadata. The paper, font, font size,
+/// language and leading defaults are overridable by the corresponding
+/// frontmatter fields; the `2em` first-line indent is fixed.
+fn write_page_setup(w: &mut impl Write, meta: &Frontmatter) -> Result<(), Error> {
+    let paper = meta.paper.as_deref().unwrap_or("a4");
+    let font = meta.font.as_deref().unwrap_or("SimSun");
+    let font_size = validate_typst_length(meta.font_size.as_deref(), "12pt");
+    let lang = meta.lang.as_deref().unwrap_or("zh");
+    let leading = validate_typst_length(meta.leading.as_deref(), "1.5em");
+
+    writeln!(
+        w,
+        r#"#set page(paper: "{}")"#,
+        escape_str_literal(paper)
+    )?;
+    writeln!(
+        w,
+        r#"#set text(font: "{}", size: {}, lang: "{}")"#,
+        escape_str_literal(font),
+        font_size,
+        escape_str_literal(lang)
+    )?;
+    writeln!(
+        w,
+        r#"#set par(leading: {}, first-line-indent: 2em)"#,
+        leading
+    )?;
+    writeln!(w)?;
+
+    if let Some(author) = &meta.author {
+        writeln!(w, r#"#let author = "{}""#, escape_str_literal(author))?;
+    }
+    if let Some(date) = &meta.date {
+        writeln!(w, r#"#let date = "{}""#, escape_str_literal(date))?;
+    }
+    if meta.author.is_some() || meta.date.is_some() {
+        writeln!(w)?;
+    }
+
+    if !meta.title.is_empty() {
+        writeln!(w, r#"#let title = "{}""#, escape_str_literal(&meta.title))?;
+        writeln!(w)?;
+        writeln!(
+            w,
+            r#"#align(center, text(size: 22pt, weight: "bold")[{}])"#,
+            Escape(&meta.title)
+        )?;
+        writeln!(w, r#"#v(1em)"#)?;
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// Parses Markdown body using pulldown-cmark and outputs Typst.
+fn render_body(w: &mut impl Write, source: &str) -> Result<(), Error> {
+    // Collected up front (rather than streamed straight from the parser) so
+    // footnote definitions can be harvested in a first pass before the real
+    // rendering pass needs them — CommonMark allows a `Event::FootnoteReference`
+    // to precede the `Tag::FootnoteDefinition` it points to.
+    let events: Vec<Event> = pulldown_cmark::Parser::new_ext(
+        source,
+        Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES | Options::ENABLE_TASKLISTS,
+    )
+    .collect();
+
+    let footnotes = collect_footnotes(&events);
+
+    // Stack of (url, title, alt-text-buffer) for images currently open; the
+    // alt text is only known once its inner `Event::Text`s have all arrived,
+    // so it's buffered until `TagEnd::Image` rather than written inline.
+    let mut image_stack: Vec<(String, String, String)> = Vec::new();
+
+    // A fenced code block's text may arrive as several `Event::Text`
+    // fragments; they're accumulated here and escaped as one Typst string
+    // literal on `TagEnd::CodeBlock`, rather than writing each fragment
+    // (and its own escaping) inline.
+    let mut code_block_active = false;
+    let mut code_block_lang: Option<String> = None;
+    let mut code_block_buf = String::new();
+
+    // Table-cell contents may contain arbitrary inline markup (emphasis,
+    // links, code, ...), so rather than duplicating the inline renderer we
+    // redirect every inline write into `table_cell_buf` while `in_cell` is
+    // set, and flush it as a single Typst `[...]` cell on `TagEnd::TableCell`.
+    let mut in_cell = false;
+    let mut in_head = false;
+    let mut table_cell_buf = String::new();
+
+    // Stack of currently open lists, innermost last, used both to pick each
+    // item's marker and to indent nested lists.
+    let mut list_stack: Vec<ListKind> = Vec::new();
+
+    // A heading's Typst label is derived from its full text, which is only
+    // known once all of its inner events have arrived, so (like table cells)
+    // its rendered markup is buffered until `TagEnd::Heading`. `heading_text`
+    // mirrors the same span as plain text, for slug derivation.
+    let mut in_heading = false;
+    let mut heading_level = 0u8;
+    let mut heading_buf = String::new();
+    let mut heading_text = String::new();
+    let mut heading_ids: HashMap<String, usize> = HashMap::new();
+
+    // `format!` first so a buffer that's itself one of `$arg` (e.g. emitting
+    // a finished `table_cell_buf` into its enclosing row) isn't borrowed
+    // mutably as a write target and immutably as an argument at once.
+    //
+    // An open image takes priority over a table cell or heading it happens
+    // to be nested in: its alt text is plain Typst text (no `#figure`/`#table`
+    // markup of its own), buffered per-image until `TagEnd::Image` builds the
+    // caption, so any inline markup inside `![...]` lands there instead of
+    // leaking into the enclosing sink.
+    macro_rules! emit {
+        ($($arg:tt)*) => {{
+            let s = format!($($arg)*);
+            if let Some((_, _, alt)) = image_stack.last_mut() {
+                alt.push_str(&s);
+            } else if in_cell {
+                table_cell_buf.push_str(&s);
+            } else if in_heading {
+                heading_buf.push_str(&s);
+            } else {
+                write!(w, "{}", s)?;
+            }
+        }};
+    }
+    macro_rules! emitln {
+        () => {{
+            if let Some((_, _, alt)) = image_stack.last_mut() {
+                alt.push('\n');
+            } else if in_cell {
+                table_cell_buf.push('\n');
+            } else if in_heading {
+                heading_buf.push('\n');
+            } else {
+                writeln!(w)?;
+            }
+        }};
+        ($($arg:tt)*) => {{
+            let s = format!($($arg)*);
+            if let Some((_, _, alt)) = image_stack.last_mut() {
+                alt.push_str(&s);
+                alt.push('\n');
+            } else if in_cell {
+                table_cell_buf.push_str(&s);
+                table_cell_buf.push('\n');
+            } else if in_heading {
+                heading_buf.push_str(&s);
+                heading_buf.push('\n');
+            } else {
+                writeln!(w, "{}", s)?;
+            }
+        }};
+    }
+
+    // Depth counter for footnote-definition blocks being skipped: their
+    // content was already rendered into `footnotes` above, so the second
+    // pass suppresses the definition body and only keeps the reference.
+    let mut skip_depth = 0u32;
+
+    for event in events {
+        if skip_depth > 0 {
+            match &event {
+                Event::Start(Tag::FootnoteDefinition(_)) => skip_depth += 1,
+                Event::End(TagEnd::FootnoteDefinition) => skip_depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Start(Tag::FootnoteDefinition(_)) => {
+                skip_depth = 1;
+            }
+            Event::FootnoteReference(name) => {
+                if let Some(body) = footnotes.get(&*name) {
+                    emit!("#footnote[{}]", body);
+                }
+            }
+
+            Event::Start(Tag::Heading { level, .. }) => {
+                in_heading = true;
+                heading_level = heading_level_to_u8(level);
+                heading_buf.clear();
+                heading_text.clear();
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                in_heading = false;
+                let slug = slugify(&heading_text);
+                if slug.is_empty() {
+                    emit!("#heading(level: {})[{}]", heading_level, heading_buf);
+                } else {
+                    let slug = derive_id(&mut heading_ids, slug);
+                    emit!(
+                        "#heading(level: {})[{}] <{}>",
+                        heading_level,
+                        heading_buf,
+                        slug
+                    );
+                }
+                emitln!();
+                emitln!();
+            }
+
+            Event::Start(Tag::Paragraph) => {}
+            Event::End(TagEnd::Paragraph) => {
+                emitln!();
+                emitln!();
+            }
+
+            Event::Text(text) => {
+                if code_block_active {
+                    code_block_buf.push_str(&text);
+                } else {
+                    if in_heading {
+                        heading_text.push_str(&text);
+                    }
+                    emit!("{}", Escape(&text));
+                }
+            }
+            Event::SoftBreak => {
+                emitln!();
+            }
+
+            Event::Start(Tag::List(start)) => {
+                // A nested list starts partway through its parent item's line
+                // (tight lists carry no blank line to force one), so break
+                // onto a fresh line before its first marker.
+                if !list_stack.is_empty() {
+                    emitln!();
+                }
+                let kind = match start {
+                    None => ListKind::Bullet,
+                    Some(1) => ListKind::Plus,
+                    Some(n) => ListKind::EnumCall(n),
+                };
+                if let ListKind::EnumCall(n) = kind {
+                    emitln!("#enum(");
+                    emitln!("  start: {},", n);
+                }
+                list_stack.push(kind);
+            }
+            Event::End(TagEnd::List(_)) => {
+                if let Some(ListKind::EnumCall(_)) = list_stack.pop() {
+                    emitln!(")");
+                }
+                emitln!();
+            }
+
+            Event::Start(Tag::Item) => {
+                let indent = "  ".repeat(list_stack.len().saturating_sub(1));
+                match list_stack.last() {
+                    Some(ListKind::Bullet) | None => emit!("{}- ", indent),
+                    Some(ListKind::Plus) => emit!("{}+ ", indent),
+                    Some(ListKind::EnumCall(_)) => emit!("{}[", indent),
+                }
+            }
+            Event::End(TagEnd::Item) => match list_stack.last() {
+                Some(ListKind::EnumCall(_)) => emitln!("],"),
+                _ => emitln!(),
+            },
+
+            Event::TaskListMarker(checked) => {
+                emit!("{} ", if checked { "\u{2612}" } else { "\u{2610}" });
+            }
+
+            Event::Start(Tag::Emphasis) => {
+                emit!("#emph[");
+            }
+            Event::End(TagEnd::Emphasis) => {
+                emit!("]");
+            }
+
+            Event::Start(Tag::Strong) => {
+                emit!("#strong[");
+            }
+            Event::End(TagEnd::Strong) => {
+                emit!("]");
+            }
+
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                emit!(r#"#link("{}")["#, escape_str_literal(&dest_url));
+            }
+            Event::End(TagEnd::Link) => {
+                emit!("]");
+            }
+
+            Event::Start(Tag::Image {
+                dest_url, title, ..
+            }) => {
+                image_stack.push((dest_url.to_string(), title.to_string(), String::new()));
+            }
+            Event::End(TagEnd::Image) => {
+                let (url, title, alt) = image_stack.pop().expect("unbalanced image tag");
+                let call = if title.is_empty() {
+                    format!(r#"image("{}")"#, escape_str_literal(&url))
+                } else {
+                    format!(
+                        r#"image("{}", alt: "{}")"#,
+                        escape_str_literal(&url),
+                        escape_str_literal(&title)
+                    )
+                };
+                if alt.is_empty() {
+                    emit!("#{}", call);
+                } else {
+                    // `alt` already holds rendered (and escaped) Typst markup,
+                    // built the same way as the main sink via `emit!`/`emitln!`
+                    // while this image was on `image_stack` — no further
+                    // escaping here.
+                    emit!(r#"#figure({}, caption: [{}])"#, call, alt);
+                }
+            }
+
+            Event::Rule => {
+                emitln!("#line(length: 100%)");
+                emitln!();
+            }
+
+            Event::Code(text) => {
+                if in_heading {
+                    heading_text.push_str(&text);
+                }
+                emit!(r#"#raw("{}")"#, escape_str_literal(&text));
+            }
+
+            Event::Start(Tag::CodeBlock(kind)) => {
+                code_block_active = true;
+                code_block_buf.clear();
+                code_block_lang = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().map(|s| s.to_string())
+                    }
+                    CodeBlockKind::Indented => None,
+                };
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                code_block_active = false;
+                let escaped = escape_str_literal(&code_block_buf);
+                match code_block_lang.take() {
+                    Some(lang) => emitln!(
+                        r#"#raw(lang: "{}", block: true, "{}")"#,
+                        escape_str_literal(&lang),
+                        escaped
+                    ),
+                    None => emitln!(r#"#raw(block: true, "{}")"#, escaped),
+                }
+                emitln!();
+            }
+
+            Event::Start(Tag::Table(alignments)) => {
+                let columns = alignments.len();
+                let mut align = alignments
+                    .iter()
+                    .map(|a| alignment_to_typst(*a))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if columns == 1 {
+                    align.push(',');
+                }
+                emitln!("#table(");
+                emitln!("  columns: {},", columns);
+                emitln!("  align: ({}),", align);
+            }
+            Event::End(TagEnd::Table) => {
+                emitln!(")");
+                emitln!();
+            }
+
+            Event::Start(Tag::TableHead) => {
+                in_head = true;
+            }
+            Event::End(TagEnd::TableHead) => {
+                in_head = false;
+                emitln!();
+            }
+
+            Event::Start(Tag::TableRow) => {}
+            Event::End(TagEnd::TableRow) => {
+                emitln!();
+            }
+
+            Event::Start(Tag::TableCell) => {
+                in_cell = true;
+                table_cell_buf.clear();
+            }
+            Event::End(TagEnd::TableCell) => {
+                in_cell = false;
+                if in_head {
+                    emit!("  [#strong[{}]], ", table_cell_buf);
+                } else {
+                    emit!("  [{}], ", table_cell_buf);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Walks the full event stream once, rendering every `Tag::FootnoteDefinition`
+/// block into a Typst string keyed by its reference name, for later lookup
+/// by `Event::FootnoteReference` in the real rendering pass.
+fn collect_footnotes(events: &[Event]) -> HashMap<String, String> {
+    let mut footnotes = HashMap::new();
+
+    let mut i = 0;
+    while i < events.len() {
+        let Event::Start(Tag::FootnoteDefinition(name)) = &events[i] else {
+            i += 1;
+            continue;
+        };
+        let name = name.to_string();
+
+        let mut depth = 1u32;
+        let mut j = i + 1;
+        while j < events.len() && depth > 0 {
+            match &events[j] {
+                Event::Start(Tag::FootnoteDefinition(_)) => depth += 1,
+                Event::End(TagEnd::FootnoteDefinition) => depth -= 1,
+                _ => {}
+            }
+            if depth > 0 {
+                j += 1;
+            }
+        }
+
+        footnotes.insert(name, render_footnote_body(&events[i + 1..j]));
+        i = j + 1;
+    }
+
+    footnotes
+}
+
+/// Renders the inline content of a footnote definition into a plain Typst
+/// string, independent of the main `render_body` loop's I/O sink.
+fn render_footnote_body(events: &[Event]) -> String {
+    let mut buf = String::new();
+    for event in events {
+        match event {
+            Event::Text(text) => write!(buf, "{}", Escape(text)).unwrap(),
+            Event::Code(text) => write!(buf, r#"#raw("{}")"#, escape_str_literal(text)).unwrap(),
+            Event::SoftBreak => buf.push(' '),
+            Event::Start(Tag::Emphasis) => buf.push_str("#emph["),
+            Event::End(TagEnd::Emphasis) => buf.push(']'),
+            Event::Start(Tag::Strong) => buf.push_str("#strong["),
+            Event::End(TagEnd::Strong) => buf.push(']'),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                write!(buf, r#"#link("{}")["#, escape_str_literal(dest_url)).unwrap()
+            }
+            Event::End(TagEnd::Link) => buf.push(']'),
+            Event::Start(Tag::Paragraph) => {}
+            Event::End(TagEnd::Paragraph) => buf.push_str("\n\n"),
+            _ => {}
+        }
+    }
+    buf.trim_end().to_string()
+}
+
+/// How an open Markdown list is rendered in Typst: a plain bullet list, an
+/// auto-numbered `+` list (ordered, starting at 1), or an explicit
+/// `#enum(start: n)` call (ordered, starting elsewhere).
+enum ListKind {
+    Bullet,
+    Plus,
+    EnumCall(u64),
+}
+
+/// Turns heading text into a Typst label: lowercase, runs of non-alphanumeric
+/// characters collapsed to a single `-`, leading/trailing dashes trimmed.
+fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    let mut last_was_dash = false;
+    for c in text.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+            last_was_dash = false;
+        } else if !last_was_dash {
+            slug.push('-');
+            last_was_dash = true;
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+/// Deduplicates a candidate id against every id handed out so far, like
+/// rustdoc's `IdMap::derive_id`: the first occurrence keeps the bare
+/// candidate, later ones get `-1`, `-2`, ... appended.
+fn derive_id(seen: &mut HashMap<String, usize>, candidate: String) -> String {
+    let Some(&count) = seen.get(&candidate) else {
+        seen.insert(candidate.clone(), 0);
+        return candidate;
+    };
+    let mut n = count;
+    let id = loop {
+        n += 1;
+        let id = format!("{}-{}", candidate, n);
+        if !seen.contains_key(&id) {
+            break id;
+        }
+    };
+    seen.insert(candidate, n);
+    seen.insert(id.clone(), 0);
+    id
+}
+
+/// Maps a Markdown column alignment to its Typst `align` keyword.
+fn alignment_to_typst(alignment: Alignment) -> &'static str {
+    match alignment {
+        Alignment::None => "auto",
+        Alignment::Left => "left",
+        Alignment::Center => "center",
+        Alignment::Right => "right",
+    }
+}
+
+/// Escapes Typst markup-reserved characters in running text, mirroring
+/// rustdoc's `html::escape::Escape` wrapper for HTML.
+struct Escape<'a>(&'a str);
+
+impl<'a> fmt::Display for Escape<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '#' => f.write_str("\\#")?,
+                '[' => f.write_str("\\[")?,
+                ']' => f.write_str("\\]")?,
+                '*' => f.write_str("\\*")?,
+                '_' => f.write_str("\\_")?,
+                '$' => f.write_str("\\$")?,
+                '@' => f.write_str("\\@")?,
+                '<' => f.write_str("\\<")?,
+                '>' => f.write_str("\\>")?,
+                '\\' => f.write_str("\\\\")?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Escapes a string for use inside a Typst string literal, e.g. the
+/// argument of `#raw("...")` or `#link("...")`.
+fn escape_str_literal(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Validates that `value` is a bare Typst length (e.g. `12pt`, `1.5em`, `100%`)
+/// before it's interpolated unquoted into a `#set` call; frontmatter isn't
+/// trusted the way the rest of the document's inline text is, so a value
+/// that doesn't match falls back to `default` instead of being emitted
+/// verbatim and potentially breaking out into arbitrary Typst markup.
+fn validate_typst_length(value: Option<&str>, default: &str) -> String {
+    match value {
+        Some(v) if is_typst_length(v) => v.to_string(),
+        _ => default.to_string(),
+    }
+}
+
+/// Matches `^\d+(\.\d+)?(pt|mm|cm|in|em|%)$`.
+fn is_typst_length(s: &str) -> bool {
+    let unit_len = ["pt", "mm", "cm", "in", "em"]
+        .iter()
+        .find(|unit| s.ends_with(**unit))
+        .map(|unit| unit.len())
+        .or_else(|| s.ends_with('%').then_some(1));
+    let Some(unit_len) = unit_len else {
+        return false;
+    };
+
+    let number = &s[..s.len() - unit_len];
+    let (int_part, frac_part) = match number.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (number, None),
+    };
+
+    !int_part.is_empty()
+        && int_part.chars().all(|c| c.is_ascii_digit())
+        && frac_part.is_none_or(|f| !f.is_empty() && f.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn heading_level_to_u8(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}